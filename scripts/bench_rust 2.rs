@@ -7,26 +7,424 @@ use std::time::{Duration, Instant};
 use std::env;
 use std::process::Command;
 
-fn bench<F>(f: F, reps: usize) -> (f64, f64)
+/// Opaque identity function the optimizer cannot see through, so a benchmarked closure's
+/// result can't be proven dead and elided.
+#[inline(never)]
+fn black_box<T>(dummy: T) -> T {
+    unsafe {
+        let ret = std::ptr::read_volatile(&dummy);
+        std::mem::forget(dummy);
+        ret
+    }
+}
+
+const WARMUP_MIN_BATCH_NS: f64 = 1_000_000.0; // ~1ms, clears timer/scheduler noise
+const SAMPLE_COUNT: usize = 50;
+const WINSORIZE_PCT: f64 = 0.05;
+
+struct BenchStats {
+    mean_ns: f64,
+    std_ns: f64,
+    median_ns: f64,
+    mad_ns: f64,
+}
+
+/// Adaptive-batch benchmark, following libtest's `Bencher`: double the batch size until a
+/// single batch clears `WARMUP_MIN_BATCH_NS`, then collect `SAMPLE_COUNT` such batches and
+/// report winsorized statistics so a handful of cold-cache/scheduler outliers can't dominate.
+fn bench<F, R>(f: F) -> BenchStats
 where
-    F: Fn() -> (),
+    F: Fn() -> R,
 {
-    let mut times = Vec::with_capacity(reps);
+    let mut batch = 1usize;
+    loop {
+        let start = Instant::now();
+        for _ in 0..batch {
+            black_box(f());
+        }
+        if start.elapsed().as_nanos() as f64 >= WARMUP_MIN_BATCH_NS {
+            break;
+        }
+        batch *= 2;
+    }
 
-    for _ in 0..reps {
+    let mut samples = Vec::with_capacity(SAMPLE_COUNT);
+    for _ in 0..SAMPLE_COUNT {
         let start = Instant::now();
-        f();
-        let elapsed = start.elapsed();
-        times.push(elapsed.as_nanos() as f64);
+        for _ in 0..batch {
+            black_box(f());
+        }
+        samples.push(start.elapsed().as_nanos() as f64 / batch as f64);
+    }
+
+    winsorized_stats(&mut samples)
+}
+
+/// Clamps the lowest/highest `WINSORIZE_PCT` of samples to the 5th/95th percentile values,
+/// then reports both mean/std and the more outlier-resistant median/MAD.
+fn winsorized_stats(samples: &mut [f64]) -> BenchStats {
+    samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let len = samples.len();
+    let cut = ((len as f64) * WINSORIZE_PCT).floor() as usize;
+    let lo = samples[cut];
+    let hi = samples[len - 1 - cut];
+    for v in samples.iter_mut() {
+        *v = v.clamp(lo, hi);
     }
 
-    let mean = times.iter().sum::<f64>() / times.len() as f64;
-    let variance = times.iter()
+    let mean = samples.iter().sum::<f64>() / len as f64;
+    let variance = samples.iter()
         .map(|&x| (x - mean).powi(2))
-        .sum::<f64>() / times.len() as f64;
+        .sum::<f64>() / len as f64;
     let std = variance.sqrt();
 
-    (mean, std)
+    let median = samples[len / 2];
+    let mut abs_devs: Vec<f64> = samples.iter().map(|&x| (x - median).abs()).collect();
+    abs_devs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mad = abs_devs[len / 2];
+
+    BenchStats { mean_ns: mean, std_ns: std, median_ns: median, mad_ns: mad }
+}
+
+/// Wraps the code PCS emitted for `program()` in a `#[no_mangle]` entry point so it can be
+/// compiled as a cdylib and invoked by symbol name, instead of being re-typed by hand here.
+/// The workload size is already baked into `program()`'s range by the caller substituting
+/// `{n}` into the comprehension before codegen, so `n` here is only an ABI parameter and
+/// isn't itself read.
+fn wrap_as_kernel(generated: &[u8]) -> Vec<u8> {
+    let mut source = generated.to_vec();
+    source.extend_from_slice(
+        b"\n#[no_mangle]\npub extern \"C\" fn pcs_kernel(n: usize) -> i64 {\n    let _ = n;\n    program() as i64\n}\n",
+    );
+    source
+}
+
+enum CaseOutcome {
+    Success {
+        stats: BenchStats,
+        value: i64,
+        scaling: Option<Vec<serde_json::Value>>,
+    },
+    Error(String),
+}
+
+/// Thread counts to sweep: powers of two below the machine's core count, followed by the core
+/// count itself, e.g. `[1, 2, 4, 8]` on an 8-core box, `[1, 2, 4, 6]` on a 6-core box.
+fn thread_sweep_counts() -> Vec<usize> {
+    let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let mut counts = Vec::new();
+    let mut threads = 1;
+    while threads < cores {
+        counts.push(threads);
+        threads *= 2;
+    }
+    counts.push(cores);
+    counts
+}
+
+/// Wraps the code PCS emitted in a `main()` that times `program()` once and prints
+/// `<result> <elapsed_ns>`, so the binary can be re-run under different `RAYON_NUM_THREADS`
+/// values to trace out the parallel codegen's scaling curve.
+fn wrap_as_timed_bin(generated: &[u8]) -> Vec<u8> {
+    let mut source = generated.to_vec();
+    source.extend_from_slice(
+        b"\nfn main() {\n    let start = std::time::Instant::now();\n    let result = program();\n    let elapsed = start.elapsed().as_nanos();\n    println!(\"{} {}\", result as i64, elapsed);\n}\n",
+    );
+    source
+}
+
+/// Runs the compiled `program()` binary once per thread count in `thread_sweep_counts()`,
+/// recording ns at each point plus speedup and efficiency (speedup / threads) relative to
+/// the single-threaded run, so PCS's rayon codegen can be checked for where it stops scaling.
+fn thread_scaling_sweep(bin_path: &str) -> Vec<serde_json::Value> {
+    let mut baseline_ns: Option<f64> = None;
+    let mut curve = Vec::new();
+
+    for threads in thread_sweep_counts() {
+        let output = Command::new(bin_path)
+            .env("RAYON_NUM_THREADS", threads.to_string())
+            .output();
+
+        let ns = match output {
+            Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+                .split_whitespace()
+                .last()
+                .and_then(|s| s.parse::<f64>().ok()),
+            _ => None,
+        };
+
+        if let Some(ns) = ns {
+            let baseline = *baseline_ns.get_or_insert(ns);
+            let speedup = baseline / ns;
+            curve.push(serde_json::json!({
+                "threads": threads,
+                "ns": ns as i64,
+                "speedup": speedup,
+                "efficiency": speedup / threads as f64
+            }));
+        }
+    }
+
+    curve
+}
+
+/// One row's worth of data for the Markdown reporter: a single `(test_name, backend, mode)`
+/// case, with stats when it succeeded.
+struct CaseRecord {
+    test_name: String,
+    backend: String,
+    mode: String,
+    mean_ns: Option<f64>,
+    std_ns: Option<f64>,
+}
+
+/// Renders a Markdown table with one row per test and one column per `backend/mode`
+/// combination seen in `records`, so adding another backend's runner later just fills in new
+/// columns instead of requiring changes here.
+fn render_markdown_report(records: &[CaseRecord]) -> String {
+    use std::collections::BTreeMap;
+
+    let mut columns: Vec<String> = Vec::new();
+    let mut rows: BTreeMap<&str, BTreeMap<String, &CaseRecord>> = BTreeMap::new();
+
+    for record in records {
+        let column = format!("{}/{}", record.backend, record.mode);
+        if !columns.contains(&column) {
+            columns.push(column.clone());
+        }
+        rows.entry(&record.test_name)
+            .or_insert_with(BTreeMap::new)
+            .insert(column, record);
+    }
+    columns.sort();
+
+    let mut out = String::new();
+    out.push_str("| test |");
+    for column in &columns {
+        out.push_str(&format!(" {} |", column));
+    }
+    out.push_str(" speedup (loops/parallel) |\n");
+
+    out.push_str("|---|");
+    for _ in &columns {
+        out.push_str("---|");
+    }
+    out.push_str("---|\n");
+
+    for (test_name, by_column) in &rows {
+        out.push_str(&format!("| {} |", test_name));
+
+        let mut loops_mean = None;
+        let mut parallel_mean = None;
+
+        for column in &columns {
+            match by_column.get(column).and_then(|r| r.mean_ns.zip(r.std_ns)) {
+                Some((mean, std)) => {
+                    out.push_str(&format!(" {:.0} \u{b1} {:.0} ns |", mean, std));
+                    let record = by_column[column];
+                    if record.mode == "loops" {
+                        loops_mean = Some(mean);
+                    } else if record.mode == "parallel" {
+                        parallel_mean = Some(mean);
+                    }
+                }
+                None => out.push_str(" - |"),
+            }
+        }
+
+        match (loops_mean, parallel_mean) {
+            (Some(l), Some(p)) if p > 0.0 => out.push_str(&format!(" {:.2}x |", l / p)),
+            _ => out.push_str(" - |"),
+        }
+
+        out.push('\n');
+    }
+
+    out
+}
+
+/// A comprehension benchmarked under every `(target, mode, parallel)` combination of `targets`
+/// and `variants`; all of them must agree on the value they compute, since they're different
+/// codegen backends for the same comprehension.
+struct Scenario {
+    test_name: &'static str,
+    /// Template containing a literal `{n}`, substituted with the workload size at run time.
+    comprehension: &'static str,
+    /// PCS `--target` values to generate this comprehension for, e.g. `"rust"`.
+    targets: &'static [&'static str],
+    variants: &'static [(&'static str, bool)],
+}
+
+/// Scratch Cargo project used to compile `--parallel` variants, whose PCS codegen pulls in
+/// rayon — a dependency a bare `rustc -O` invocation has no way to resolve.
+const SCRATCH_CRATE_DIR: &str = "generated/rust_bench_crate";
+
+/// Writes `source` into a throwaway Cargo project depending on rayon and builds it in
+/// release mode, returning the path to the resulting artifact. `crate_type` is `"cdylib"`
+/// for the dlopen'd kernel or `"bin"` for the timed scaling binary; the crate is named
+/// `rust_bench` and the returned path applies the platform's dylib prefix/suffix for the
+/// cdylib, so callers don't need to know the platform's dylib naming.
+fn compile_parallel_crate(source: &[u8], crate_type: &str) -> Result<String, String> {
+    let src_dir = format!("{}/src", SCRATCH_CRATE_DIR);
+    std::fs::create_dir_all(&src_dir)
+        .map_err(|e| format!("Failed to create scratch crate dir: {}", e))?;
+
+    let lib_section = if crate_type == "cdylib" {
+        "\n[lib]\ncrate-type = [\"cdylib\"]\n"
+    } else {
+        ""
+    };
+    let manifest = format!(
+        "[package]\nname = \"rust_bench\"\nversion = \"0.0.0\"\nedition = \"2021\"\n\n[dependencies]\nrayon = \"1\"\n{}",
+        lib_section
+    );
+    std::fs::write(format!("{}/Cargo.toml", SCRATCH_CRATE_DIR), manifest)
+        .map_err(|e| format!("Failed to write scratch Cargo.toml: {}", e))?;
+
+    // Only one of lib.rs/main.rs should exist at a time: cargo auto-detects both as targets
+    // from their mere presence, and a stale one left over from the other crate_type would
+    // get built (and fail) alongside the one we actually want.
+    let (entry_file, stale_file) = if crate_type == "cdylib" {
+        ("lib.rs", "main.rs")
+    } else {
+        ("main.rs", "lib.rs")
+    };
+    let _ = std::fs::remove_file(format!("{}/{}", src_dir, stale_file));
+    std::fs::write(format!("{}/{}", src_dir, entry_file), source)
+        .map_err(|e| format!("Failed to write scratch crate source: {}", e))?;
+
+    let build = Command::new("cargo")
+        .args(&[
+            "build",
+            "--release",
+            "--manifest-path",
+            &format!("{}/Cargo.toml", SCRATCH_CRATE_DIR),
+        ])
+        .output();
+
+    match build {
+        Ok(output) if output.status.success() => Ok(if crate_type == "cdylib" {
+            format!(
+                "{}/target/release/{}rust_bench{}",
+                SCRATCH_CRATE_DIR,
+                env::consts::DLL_PREFIX,
+                env::consts::DLL_SUFFIX
+            )
+        } else {
+            format!("{}/target/release/rust_bench", SCRATCH_CRATE_DIR)
+        }),
+        Ok(output) => Err(format!(
+            "cargo build failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )),
+        Err(e) => Err(format!("Failed to invoke cargo: {}", e)),
+    }
+}
+
+/// Generates, compiles and dlopens the PCS kernel for one `(comprehension, target, mode,
+/// parallel)` case, then benchmarks it and records the value it returned. Failures to
+/// generate, compile, load or resolve the kernel become `CaseOutcome::Error` so callers can
+/// report them uniformly regardless of output format; a failure to build the parallel
+/// scaling binary only leaves `scaling` as `None`.
+///
+/// The dlopen/compile machinery below is Rust-specific (it links the generated code as a
+/// cdylib and resolves a `pcs_kernel` symbol), so in practice `target` is always `"rust"` in
+/// this harness today — but it's threaded through rather than hardcoded so a future target
+/// with its own run step can be added without re-plumbing the comprehension/backend matrix.
+///
+/// `comprehension` is a template containing a literal `{n}`, substituted with the actual
+/// workload size before it's handed to PCS, so `PCS_BENCH_N` really does control how much
+/// work the generated kernel does rather than just being reported alongside a fixed range.
+fn run_case(comprehension: &str, target: &str, parallel: bool, n: usize) -> CaseOutcome {
+    let code = comprehension.replace("{n}", &n.to_string());
+
+    let mut cmd = Command::new("python3");
+    cmd.args(&[
+        "-m", "pcs",
+        "--code", &code,
+        "--target", target,
+    ]);
+
+    if parallel {
+        cmd.arg("--parallel");
+    }
+
+    let output = match cmd.output() {
+        Ok(output) => output,
+        Err(e) => return CaseOutcome::Error(format!("Failed to generate Rust code: {}", e)),
+    };
+
+    if !output.status.success() {
+        return CaseOutcome::Error(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+
+    let kernel_source = wrap_as_kernel(&output.stdout);
+
+    // `--parallel` codegen links rayon, which a bare `rustc` invocation can't resolve without
+    // a manifest, so build those cases through a scratch Cargo project instead.
+    let kernel_path = if parallel {
+        match compile_parallel_crate(&kernel_source, "cdylib") {
+            Ok(path) => path,
+            Err(e) => return CaseOutcome::Error(format!("Compilation failed: {}", e)),
+        }
+    } else {
+        if let Err(e) = std::fs::write("generated/rust_bench.rs", &kernel_source) {
+            return CaseOutcome::Error(format!("Failed to write generated Rust code: {}", e));
+        }
+
+        let compile_result = Command::new("rustc")
+            .args(&[
+                "-O",
+                "--crate-type=cdylib",
+                "generated/rust_bench.rs",
+                "-o",
+                "generated/rust_bench",
+            ])
+            .output();
+
+        match compile_result {
+            Err(e) => return CaseOutcome::Error(format!("Compilation failed: {}", e)),
+            Ok(output) if !output.status.success() => {
+                return CaseOutcome::Error(format!(
+                    "Compilation failed: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                ))
+            }
+            Ok(_) => {}
+        }
+
+        "generated/rust_bench".to_string()
+    };
+
+    let lib = match unsafe { libloading::Library::new(&kernel_path) } {
+        Ok(lib) => lib,
+        Err(e) => return CaseOutcome::Error(format!("Failed to load {}: {}", kernel_path, e)),
+    };
+
+    let kernel: libloading::Symbol<unsafe extern "C" fn(usize) -> i64> =
+        match unsafe { lib.get(b"pcs_kernel\0") } {
+            Ok(sym) => sym,
+            Err(e) => return CaseOutcome::Error(format!("Failed to resolve pcs_kernel: {}", e)),
+        };
+
+    // Capture the returned value once for cross-backend correctness checks, then benchmark
+    // the actual generated kernel, not a hand-copied simulation of it.
+    let value = unsafe { kernel(n) };
+    let stats = bench(|| unsafe { kernel(n) });
+
+    // For parallel cases, separately compile a timed binary we can re-spawn under varying
+    // RAYON_NUM_THREADS to trace out the scaling curve (dlopen'd symbols all share one
+    // process and can't observe a changed thread count after rayon's pool is built).
+    let scaling = if parallel {
+        match compile_parallel_crate(&wrap_as_timed_bin(&output.stdout), "bin") {
+            Ok(bin_path) => Some(thread_scaling_sweep(&bin_path)),
+            Err(_) => None,
+        }
+    } else {
+        None
+    };
+
+    CaseOutcome::Success { stats, value, scaling }
 }
 
 fn main() {
@@ -39,137 +437,208 @@ fn main() {
         .parse()
         .unwrap_or(1000000);
 
-    // Test cases to benchmark
-    let test_cases = vec![
-        ("sum_even_squares", "loops", false),
-        ("sum_even_squares", "parallel", true),
-    ];
-
-    for (test_name, mode, parallel) in test_cases {
-        // Generate Rust code using PCS
-        let mut cmd = Command::new("python3");
-        cmd.args(&[
-            "-m", "pcs",
-            "--code", "sum(i*i for i in range(1, 1000000) if i%2==0)",
-            "--target", "rust",
-        ]);
-
-        if parallel {
-            cmd.arg("--parallel");
-        }
+    let args: Vec<String> = env::args().collect();
+    let json_events = args.windows(2).any(|w| w[0] == "--format" && w[1] == "json-events");
+    let report_path = args
+        .iter()
+        .position(|a| a == "--report")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
 
-        let output = cmd.output().expect("Failed to generate Rust code");
-
-        if !output.status.success() {
-            let error_result = serde_json::json!({
-                "commit": commit,
-                "timestamp": timestamp,
-                "os": os,
-                "cpu": cpu,
-                "backend": "rust",
-                "test": test_name,
-                "mode": mode,
-                "parallel": parallel,
-                "n": n,
-                "error": String::from_utf8_lossy(&output.stderr)
-            });
-            println!("{}", error_result);
-            continue;
-        }
+    // Matrix of comprehensions to benchmark, each run under every `(target, mode, parallel)`
+    // combination so the same comprehension's backends can be checked for correctness against
+    // each other.
+    let scenarios = vec![Scenario {
+        test_name: "sum_even_squares",
+        comprehension: "sum(i*i for i in range(1, {n}) if i%2==0)",
+        targets: &["rust"],
+        variants: &[("loops", false), ("parallel", true)],
+    }];
 
-        // Write generated code to file
-        std::fs::write("generated/rust_bench.rs", output.stdout)
-            .expect("Failed to write generated Rust code");
+    let case_count: usize = scenarios
+        .iter()
+        .map(|s| s.targets.len() * s.variants.len())
+        .sum();
+    if json_events {
+        println!(
+            "{}",
+            serde_json::json!({"type": "suite", "event": "started", "test_count": case_count})
+        );
+    }
 
-        // Compile the generated code
-        let compile_result = Command::new("rustc")
-            .args(&["-O", "generated/rust_bench.rs", "-o", "target/rust_bench"])
-            .output();
+    let mut passed = 0usize;
+    let mut failed = 0usize;
+    let mut records: Vec<CaseRecord> = Vec::new();
 
-        if let Err(e) = compile_result {
-            let error_result = serde_json::json!({
-                "commit": commit,
-                "timestamp": timestamp,
-                "os": os,
-                "cpu": cpu,
-                "backend": "rust",
-                "test": test_name,
-                "mode": mode,
-                "parallel": parallel,
-                "n": n,
-                "error": format!("Compilation failed: {}", e)
-            });
-            println!("{}", error_result);
-            continue;
-        }
+    for scenario in &scenarios {
+        let test_name = scenario.test_name;
+        // Keyed by "{target}/{mode}" so parity is checked across every backend this
+        // comprehension was generated for, not just across `loops`/`parallel`.
+        let mut values: Vec<(String, i64)> = Vec::new();
 
-        // Run the benchmark
-        let run_result = Command::new("./target/rust_bench")
-            .output();
+        for &target in scenario.targets {
+            for &(mode, parallel) in scenario.variants {
+                let case_name = format!("{}::{}::{}", test_name, target, mode);
+                let label = format!("{}/{}", target, mode);
 
-        if let Ok(output) = run_result {
-            if output.status.success() {
-                // Parse the output to get timing results
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                let lines: Vec<&str> = output_str.lines().collect();
-
-                // Simple benchmark of the generated function
-                let (mean, std) = bench(|| {
-                    // This would call the actual generated function
-                    // For now, we'll simulate the work
-                    let mut sum = 0;
-                    for i in 1..n {
-                        if i % 2 == 0 {
-                            sum += i * i;
+                if json_events {
+                    println!(
+                        "{}",
+                        serde_json::json!({"type": "bench", "event": "started", "name": case_name})
+                    );
+                }
+
+                match run_case(scenario.comprehension, target, parallel, n) {
+                    CaseOutcome::Success { stats, value, scaling } => {
+                        passed += 1;
+                        values.push((label, value));
+                        records.push(CaseRecord {
+                            test_name: test_name.to_string(),
+                            backend: target.to_string(),
+                            mode: mode.to_string(),
+                            mean_ns: Some(stats.mean_ns),
+                            std_ns: Some(stats.std_ns),
+                        });
+                        if json_events {
+                            let mib_per_second = (n as f64 * std::mem::size_of::<i64>() as f64)
+                                / (stats.median_ns / 1e9)
+                                / (1024.0 * 1024.0);
+                            println!(
+                                "{}",
+                                serde_json::json!({
+                                    "type": "bench",
+                                    "name": case_name,
+                                    "median": stats.median_ns as i64,
+                                    "deviation": stats.std_ns as i64,
+                                    "mib_per_second": mib_per_second
+                                })
+                            );
+                        } else {
+                            println!(
+                                "{}",
+                                serde_json::json!({
+                                    "commit": commit,
+                                    "timestamp": timestamp,
+                                    "os": os,
+                                    "cpu": cpu,
+                                    "backend": target,
+                                    "test": test_name,
+                                    "mode": mode,
+                                    "parallel": parallel,
+                                    "n": n,
+                                    "value": value,
+                                    "mean_ns": stats.mean_ns as i64,
+                                    "std_ns": stats.std_ns as i64,
+                                    "median_ns": stats.median_ns as i64,
+                                    "mad_ns": stats.mad_ns as i64,
+                                    "scaling": scaling
+                                })
+                            );
                         }
                     }
-                    sum
-                }, 10);
-
-                let result = serde_json::json!({
-                    "commit": commit,
-                    "timestamp": timestamp,
-                    "os": os,
-                    "cpu": cpu,
-                    "backend": "rust",
-                    "test": test_name,
-                    "mode": mode,
-                    "parallel": parallel,
-                    "n": n,
-                    "mean_ns": mean as i64,
-                    "std_ns": std as i64
-                });
-
-                println!("{}", result);
-            } else {
-                let error_result = serde_json::json!({
-                    "commit": commit,
-                    "timestamp": timestamp,
-                    "os": os,
-                    "cpu": cpu,
-                    "backend": "rust",
-                    "test": test_name,
-                    "mode": mode,
-                    "parallel": parallel,
-                    "n": n,
-                    "error": String::from_utf8_lossy(&output.stderr)
-                });
-                println!("{}", error_result);
+                    CaseOutcome::Error(err) => {
+                        failed += 1;
+                        records.push(CaseRecord {
+                            test_name: test_name.to_string(),
+                            backend: target.to_string(),
+                            mode: mode.to_string(),
+                            mean_ns: None,
+                            std_ns: None,
+                        });
+                        if json_events {
+                            println!(
+                                "{}",
+                                serde_json::json!({"type": "test_output", "name": case_name, "stderr": err})
+                            );
+                            println!(
+                                "{}",
+                                serde_json::json!({"type": "test", "name": case_name, "event": "failed"})
+                            );
+                        } else {
+                            println!(
+                                "{}",
+                                serde_json::json!({
+                                    "commit": commit,
+                                    "timestamp": timestamp,
+                                    "os": os,
+                                    "cpu": cpu,
+                                    "backend": target,
+                                    "test": test_name,
+                                    "mode": mode,
+                                    "parallel": parallel,
+                                    "n": n,
+                                    "error": err
+                                })
+                            );
+                        }
+                    }
+                }
             }
-        } else {
-            let error_result = serde_json::json!({
-                "commit": commit,
-                "timestamp": timestamp,
-                "os": os,
-                "cpu": cpu,
-                "backend": "rust",
-                "test": test_name,
-                "mode": mode,
-                "parallel": parallel,
-                "n": n,
-                "error": "Failed to run benchmark"
-            });
-            println!("{}", error_result);
         }
+
+        // All variants of a comprehension are different codegen backends for the same
+        // computation; they must agree, or PCS has a semantic parity bug between them. Count
+        // a mismatch as a failure so a CI gate watching passed/failed actually sees it.
+        if let Some((first_label, first_value)) = values.first() {
+            if let Some((mismatched_label, mismatched_value)) =
+                values.iter().find(|(_, v)| v != first_value)
+            {
+                failed += 1;
+                if json_events {
+                    let case_name = format!("{}::correctness", test_name);
+                    println!(
+                        "{}",
+                        serde_json::json!({
+                            "type": "test_output",
+                            "name": case_name,
+                            "stderr": format!(
+                                "mismatch: {} = {} but {} = {}",
+                                first_label, first_value, mismatched_label, mismatched_value
+                            )
+                        })
+                    );
+                    println!(
+                        "{}",
+                        serde_json::json!({"type": "test", "name": case_name, "event": "failed"})
+                    );
+                } else {
+                    println!(
+                        "{}",
+                        serde_json::json!({
+                            "commit": commit,
+                            "timestamp": timestamp,
+                            "test": test_name,
+                            "correctness": "mismatch",
+                            "expected_variant": first_label,
+                            "expected_value": first_value,
+                            "mismatched_variant": mismatched_label,
+                            "mismatched_value": mismatched_value
+                        })
+                    );
+                }
+            }
+        }
+    }
+
+    if json_events {
+        println!(
+            "{}",
+            serde_json::json!({
+                "type": "suite",
+                "event": if failed == 0 { "ok" } else { "failed" },
+                "passed": passed,
+                "failed": failed
+            })
+        );
+    }
+
+    // The Markdown report goes to the --report file when one is given; otherwise it goes to
+    // stderr, except in json-events mode, where a consumer only wants the event stream.
+    match (json_events, report_path) {
+        (true, None) => {}
+        (_, Some(path)) => {
+            std::fs::write(&path, render_markdown_report(&records)).expect("Failed to write report")
+        }
+        (false, None) => eprintln!("{}", render_markdown_report(&records)),
     }
 }